@@ -1,8 +1,10 @@
 mod completion;
 mod download_whisper;
+mod find;
 
 pub use completion::CompletionArgs;
 pub use download_whisper::DownloadWhisperArgs;
+pub use find::FindArgs;
 
 use crate::Command;
 
@@ -18,6 +20,10 @@ pub async fn handle_auxiliary_command(command: Option<&Command>) -> anyhow::Resu
             download_whisper::handle(args).await?;
             Ok(true)
         }
+        Some(Command::Find(args)) => {
+            find::handle(args)?;
+            Ok(true)
+        }
         _ => Ok(false),
     }
 }
@@ -0,0 +1,51 @@
+use anyhow::Context;
+use clap::Args;
+
+use crate::index::Index;
+
+#[derive(Clone, Debug, Args)]
+pub struct FindArgs {
+    #[arg(help = "Search query", required_unless_present = "rebuild")]
+    query: Option<String>,
+
+    #[arg(
+        short = 'n',
+        long = "limit",
+        default_value_t = 10,
+        help = "Maximum number of results"
+    )]
+    limit: usize,
+
+    #[arg(long = "json", help = "Print results as JSON")]
+    json: bool,
+
+    #[arg(
+        long = "rebuild",
+        help = "Rebuild the index from stored records (e.g., after upgrading nosy)"
+    )]
+    rebuild: bool,
+}
+
+pub fn handle(args: &FindArgs) -> anyhow::Result<()> {
+    let index = Index::from_state_dir()?;
+    if args.rebuild {
+        let count = index.rebuild()?;
+        eprintln!("Rebuilt index with {count} records");
+    }
+    let Some(query) = &args.query else {
+        return Ok(());
+    };
+
+    let hits = index.search(query, args.limit)?;
+    if args.json {
+        let json = serde_json::to_string_pretty(&hits).context("failed to serialize results")?;
+        println!("{json}");
+        return Ok(());
+    }
+    for hit in hits {
+        println!("{:.3}  {}", hit.score, hit.source);
+        println!("       output: {}", hit.output.display());
+        println!("       {}", hit.snippet);
+    }
+    Ok(())
+}
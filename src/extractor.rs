@@ -1,5 +1,6 @@
 use std::{
     collections::HashMap,
+    fmt,
     path::{Path, PathBuf},
     sync::LazyLock,
 };
@@ -11,10 +12,26 @@ use indicatif::ProgressBar;
 pub mod html;
 pub mod pandoc;
 pub mod pdf;
+pub mod quality;
 pub mod whisper;
 
 pub const EXTRACTED_CONTENT_FILENAME: &str = "ext";
 
+/// Process exit code when no usable content could be extracted
+pub const EXTRACTION_FAILURE_EXIT_CODE: u8 = 3;
+
+/// Context attached to errors caused by the content itself (e.g., empty or garbled output),
+/// as opposed to setup errors such as a missing tool or model.
+/// main maps errors carrying it to EXTRACTION_FAILURE_EXIT_CODE.
+#[derive(Debug)]
+pub struct ExtractionFailure;
+
+impl fmt::Display for ExtractionFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("failed to extract usable content")
+    }
+}
+
 /// A trait for extracting text content from various formats.
 #[async_trait::async_trait]
 pub trait Extractor {
@@ -5,7 +5,7 @@ use indicatif::ProgressBar;
 use readabilityrs::Readability;
 
 use crate::{
-    extractor::{EXTRACTED_CONTENT_FILENAME, Extractor},
+    extractor::{EXTRACTED_CONTENT_FILENAME, ExtractionFailure, Extractor},
     file_type::{Extension, Mime},
 };
 
@@ -43,6 +43,6 @@ impl Extractor for HtmlExtractor {
                 .context("failed to write extracted text content")?;
             return Ok(extracted_path);
         }
-        Err(anyhow::anyhow!("failed to extract text from HTML content"))
+        Err(anyhow::anyhow!("failed to extract text from HTML content").context(ExtractionFailure))
     }
 }
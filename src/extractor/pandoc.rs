@@ -8,7 +8,7 @@ use log::debug;
 use crate::cli_command::CliCommand;
 use crate::validate::validate_command_executable;
 use crate::{
-    extractor::{EXTRACTED_CONTENT_FILENAME, Extractor},
+    extractor::{EXTRACTED_CONTENT_FILENAME, ExtractionFailure, Extractor},
     file_type::{Extension, Mime},
 };
 
@@ -112,7 +112,7 @@ impl Extractor for PandocExtractor {
             .to_string();
 
         if text.is_empty() {
-            Err(anyhow::anyhow!("pandoc produced empty output"))
+            Err(anyhow::anyhow!("pandoc produced empty output").context(ExtractionFailure))
         } else {
             let extracted_path = workdir.join(EXTRACTED_CONTENT_FILENAME);
             tokio::fs::write(&extracted_path, text)
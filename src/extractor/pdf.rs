@@ -4,7 +4,7 @@ use anyhow::Context;
 use indicatif::ProgressBar;
 
 use crate::{
-    extractor::{EXTRACTED_CONTENT_FILENAME, Extractor},
+    extractor::{EXTRACTED_CONTENT_FILENAME, ExtractionFailure, Extractor},
     file_type::{Extension, Mime},
 };
 
@@ -28,7 +28,8 @@ impl Extractor for PdfExtractor {
         // Write extracted text to output file
         let text = text.trim().to_string();
         if text.is_empty() {
-            Err(anyhow::anyhow!("failed to extract text from PDF content"))
+            Err(anyhow::anyhow!("failed to extract text from PDF content")
+                .context(ExtractionFailure))
         } else {
             let extracted_path = workdir.join(EXTRACTED_CONTENT_FILENAME);
             tokio::fs::write(&extracted_path, text)
@@ -0,0 +1,431 @@
+use clap::Args;
+
+use crate::{extractor::Kind, text::is_unspaced_script};
+
+/// Default minimum number of extracted characters required to summarize
+pub const DEFAULT_MIN_CONTENT_CHARS: usize = 200;
+
+/// Quality score below which extracted content is considered too thin or garbled
+pub const MIN_QUALITY_SCORE: f64 = 0.25;
+
+/// Sentences up to this length can be flagged as boilerplate as a whole
+const BOILERPLATE_MAX_SENTENCE_CHARS: usize = 80;
+
+/// Minimum share of a sentence covered by boilerplate phrases to flag it as a whole
+const BOILERPLATE_MIN_COVERAGE: f64 = 0.2;
+
+/// Phrases commonly found in cookie banners, navigation and other page chrome
+const BOILERPLATE_PHRASES: &[&str] = &[
+    "we use cookies",
+    "use of cookies",
+    "accept cookies",
+    "cookie policy",
+    "cookie settings",
+    "accept all",
+    "reject all",
+    "privacy policy",
+    "terms of service",
+    "terms of use",
+    "all rights reserved",
+    "sign in",
+    "log in",
+    "subscribe",
+    "enable javascript",
+    "your browser",
+    "we value your privacy",
+    "skip to content",
+];
+
+/// Options to guard summarization against thin extracted content
+#[derive(Clone, Debug, Args)]
+pub struct ContentGuardOptions {
+    #[arg(
+        long = "min-content-chars",
+        value_name = "N",
+        default_value_t = DEFAULT_MIN_CONTENT_CHARS,
+        help = "Minimum number of extracted characters required to summarize"
+    )]
+    pub min_content_chars: usize,
+
+    #[arg(
+        long = "allow-thin-content",
+        help = "Summarize even if extracted content looks too thin or garbled"
+    )]
+    pub allow_thin_content: bool,
+}
+
+/// Quality metrics of extracted content
+#[derive(Clone, Debug, PartialEq)]
+pub struct QualityReport {
+    /// Number of extracted characters
+    pub chars: usize,
+    /// Extracted characters relative to the expected amount for the extractor kind (0.0..=1.0)
+    pub size_factor: f64,
+    /// Ratio of dictionary-ish words to all tokens
+    pub word_ratio: f64,
+    /// Ratio of replacement, control and private-use characters to all characters
+    pub replacement_density: f64,
+    /// Ratio of characters in boilerplate sentences
+    pub boilerplate_ratio: f64,
+    /// Combined score (0.0..=1.0)
+    pub score: f64,
+}
+
+impl QualityReport {
+    /// Evaluate extracted text against the size of the input it was extracted from.
+    pub fn evaluate(text: &str, input_bytes: u64, kind: &Kind) -> Self {
+        let chars = text.chars().count();
+        let size_factor = size_factor(chars, input_bytes, kind);
+        let word_ratio = word_ratio(text);
+        let replacement_density = replacement_density(text);
+        let boilerplate_ratio = boilerplate_ratio(text);
+        let score = size_factor
+            * word_ratio
+            * (1.0 - replacement_density * 10.0).max(0.0)
+            * (1.0 - boilerplate_ratio);
+        Self {
+            chars,
+            size_factor,
+            word_ratio,
+            replacement_density,
+            boilerplate_ratio,
+            score,
+        }
+    }
+
+    /// Return why the content is too thin to summarize, if it is.
+    /// A low score takes precedence because lowering the minimum length does not help then.
+    pub fn thin_reason(&self, min_chars: usize) -> Option<ThinReason> {
+        if self.score < MIN_QUALITY_SCORE {
+            Some(ThinReason::LowScore)
+        } else if self.chars < min_chars {
+            Some(ThinReason::TooShort)
+        } else {
+            None
+        }
+    }
+}
+
+/// Reason extracted content is considered too thin
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ThinReason {
+    /// Fewer characters than --min-content-chars
+    TooShort,
+    /// Quality score below MIN_QUALITY_SCORE
+    LowScore,
+}
+
+/// Typical ratio of extracted characters to input bytes per extractor kind.
+/// None if the ratio is meaningless (e.g., pass-through or audio).
+fn expected_size_ratio(kind: &Kind) -> Option<f64> {
+    match kind {
+        Kind::HtmlNative => Some(0.01),
+        Kind::PdfNative => Some(0.02),
+        Kind::Pandoc => Some(0.02),
+        _ => None,
+    }
+}
+
+/// Score extracted size against the expected ratio for the extractor kind.
+/// Square root softens the penalty for inputs heavy in markup or images.
+pub fn size_factor(chars: usize, input_bytes: u64, kind: &Kind) -> f64 {
+    match expected_size_ratio(kind) {
+        Some(expected) if input_bytes > 0 => {
+            let ratio = chars as f64 / input_bytes as f64;
+            (ratio / expected).min(1.0).sqrt()
+        }
+        _ => 1.0,
+    }
+}
+
+/// Return true for ASCII, general, CJK and fullwidth punctuation
+fn is_punctuation(c: char) -> bool {
+    c.is_ascii_punctuation()
+        || matches!(c,
+            '¡' | '«' | '»' | '¿'
+            | '\u{2010}'..='\u{2027}' // General punctuation (dashes, quotes, ellipsis)
+            | '\u{2030}'..='\u{205E}'
+            | '\u{3000}'..='\u{303F}' // CJK symbols and punctuation
+            | '\u{30FB}' // Katakana middle dot
+            | '\u{FF01}'..='\u{FF0F}' // Fullwidth punctuation
+            | '\u{FF1A}'..='\u{FF20}'
+            | '\u{FF3B}'..='\u{FF40}'
+            | '\u{FF5B}'..='\u{FF65}'
+        )
+}
+
+/// Split text into word candidates.
+/// Whitespace and non-ASCII punctuation separate candidates,
+/// and each Chinese or Japanese character is a candidate on its own.
+fn word_candidates(text: &str) -> Vec<&str> {
+    let mut candidates = Vec::new();
+    let mut start = None;
+    for (idx, c) in text.char_indices() {
+        let unspaced = is_unspaced_script(c);
+        if c.is_whitespace() || unspaced || (!c.is_ascii() && is_punctuation(c)) {
+            if let Some(start) = start.take() {
+                candidates.push(&text[start..idx]);
+            }
+            if unspaced {
+                candidates.push(&text[idx..idx + c.len_utf8()]);
+            }
+        } else if start.is_none() {
+            start = Some(idx);
+        }
+    }
+    if let Some(start) = start {
+        candidates.push(&text[start..]);
+    }
+    candidates
+}
+
+/// Ratio of dictionary-ish words to all word candidates.
+/// Numeric tokens (e.g., years, prices) are ignored.
+pub fn word_ratio(text: &str) -> f64 {
+    let mut words = 0;
+    let mut total = 0;
+    for token in word_candidates(text) {
+        let token = token.trim_matches(is_punctuation);
+        if token.is_empty()
+            || token
+                .chars()
+                .all(|c| c.is_ascii_digit() || matches!(c, ',' | '.' | '%'))
+        {
+            continue;
+        }
+        total += 1;
+        if token
+            .chars()
+            .all(|c| c.is_alphabetic() || matches!(c, '-' | '\'' | '’'))
+        {
+            words += 1;
+        }
+    }
+    if total == 0 {
+        0.0
+    } else {
+        words as f64 / total as f64
+    }
+}
+
+/// Ratio of replacement (U+FFFD), control and private-use characters to all characters.
+pub fn replacement_density(text: &str) -> f64 {
+    let mut bad = 0;
+    let mut total = 0;
+    for c in text.chars() {
+        total += 1;
+        if c == char::REPLACEMENT_CHARACTER
+            || (c.is_control() && !matches!(c, '\n' | '\r' | '\t'))
+            || ('\u{E000}'..='\u{F8FF}').contains(&c)
+        {
+            bad += 1;
+        }
+    }
+    if total == 0 {
+        0.0
+    } else {
+        bad as f64 / total as f64
+    }
+}
+
+/// Ratio of boilerplate characters to all non-blank characters.
+///
+/// Text is split into sentences (extracted text is often a single line).
+/// Short sentences mostly covered by boilerplate phrases count as a whole,
+/// others only count the matched phrases, so articles merely mentioning
+/// e.g. cookies or sign-in are not penalized.
+/// Phrases match whole words only (e.g., "log in" does not match "catalog in").
+pub fn boilerplate_ratio(text: &str) -> f64 {
+    let mut boilerplate = 0;
+    let mut total = 0;
+    for sentence in text
+        .split_inclusive(['\n', '.', '!', '?', '。', '！', '？'])
+        .map(str::trim)
+        .filter(|sentence| !sentence.is_empty())
+    {
+        let len = sentence.chars().count();
+        total += len;
+        let matched = boilerplate_chars(sentence).min(len);
+        if len <= BOILERPLATE_MAX_SENTENCE_CHARS
+            && matched as f64 >= len as f64 * BOILERPLATE_MIN_COVERAGE
+        {
+            boilerplate += len;
+        } else {
+            boilerplate += matched;
+        }
+    }
+    if total == 0 {
+        0.0
+    } else {
+        boilerplate as f64 / total as f64
+    }
+}
+
+/// Number of characters of boilerplate phrases occurring as word sequences in the sentence
+fn boilerplate_chars(sentence: &str) -> usize {
+    let words = word_candidates(sentence)
+        .into_iter()
+        .map(|word| word.trim_matches(is_punctuation).to_lowercase())
+        .collect::<Vec<_>>();
+    BOILERPLATE_PHRASES
+        .iter()
+        .map(|phrase| {
+            let phrase_words = phrase.split(' ').collect::<Vec<_>>();
+            let count = words
+                .windows(phrase_words.len())
+                .filter(|window| *window == phrase_words.as_slice())
+                .count();
+            count * phrase.chars().count()
+        })
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GOOD_ARTICLE: &str = include_str!("../../tests/fixtures/quality/good_article.txt");
+    const COOKIE_WALL: &str = include_str!("../../tests/fixtures/quality/cookie_wall.txt");
+    const MISDECODED_PDF: &str = include_str!("../../tests/fixtures/quality/misdecoded_pdf.txt");
+    const JAPANESE_ARTICLE: &str =
+        include_str!("../../tests/fixtures/quality/japanese_article.txt");
+    const COOKIE_ARTICLE: &str = include_str!("../../tests/fixtures/quality/cookie_article.txt");
+
+    mod quality_report {
+        use super::*;
+
+        mod evaluate {
+            use super::*;
+
+            #[test]
+            fn test_good_article() {
+                let report = QualityReport::evaluate(GOOD_ARTICLE, 80_000, &Kind::HtmlNative);
+                assert!(report.score > 0.7, "{report:?}");
+                assert_eq!(report.thin_reason(DEFAULT_MIN_CONTENT_CHARS), None);
+            }
+
+            #[test]
+            fn test_single_line_article_with_newsletter_prompt() {
+                let text = format!(
+                    "{} Subscribe to our newsletter.",
+                    GOOD_ARTICLE
+                        .split_whitespace()
+                        .collect::<Vec<_>>()
+                        .join(" ")
+                );
+                let report = QualityReport::evaluate(&text, 80_000, &Kind::HtmlNative);
+                assert!(report.boilerplate_ratio < 0.1, "{report:?}");
+                assert_eq!(report.thin_reason(DEFAULT_MIN_CONTENT_CHARS), None);
+            }
+
+            #[test]
+            fn test_article_about_cookies() {
+                let report = QualityReport::evaluate(COOKIE_ARTICLE, 80_000, &Kind::HtmlNative);
+                assert!(report.boilerplate_ratio < 0.1, "{report:?}");
+                assert_eq!(report.thin_reason(DEFAULT_MIN_CONTENT_CHARS), None);
+            }
+
+            #[test]
+            fn test_japanese_article() {
+                let report = QualityReport::evaluate(JAPANESE_ARTICLE, 30_000, &Kind::HtmlNative);
+                assert!(report.word_ratio > 0.9, "{report:?}");
+                assert!(report.score > 0.7, "{report:?}");
+                assert_eq!(report.thin_reason(DEFAULT_MIN_CONTENT_CHARS), None);
+            }
+
+            #[test]
+            fn test_cookie_wall() {
+                let report = QualityReport::evaluate(COOKIE_WALL, 2_000_000, &Kind::HtmlNative);
+                assert!(report.size_factor < 0.2, "{report:?}");
+                assert!(report.boilerplate_ratio > 0.4, "{report:?}");
+                assert_eq!(report.thin_reason(0), Some(ThinReason::LowScore));
+            }
+
+            #[test]
+            fn test_misdecoded_pdf() {
+                let report = QualityReport::evaluate(MISDECODED_PDF, 30_000, &Kind::PdfNative);
+                assert!(report.word_ratio < 0.3, "{report:?}");
+                assert!(report.replacement_density > 0.05, "{report:?}");
+                assert_eq!(report.thin_reason(0), Some(ThinReason::LowScore));
+            }
+
+            #[test]
+            fn test_empty() {
+                let report = QualityReport::evaluate("", 0, &Kind::PlainText);
+                assert_eq!(report.score, 0.0);
+                assert_eq!(report.thin_reason(0), Some(ThinReason::LowScore));
+            }
+        }
+
+        mod thin_reason {
+            use super::*;
+
+            #[test]
+            fn test_too_short() {
+                let report = QualityReport::evaluate(GOOD_ARTICLE, 80_000, &Kind::HtmlNative);
+                assert_eq!(
+                    report.thin_reason(report.chars + 1),
+                    Some(ThinReason::TooShort)
+                );
+            }
+        }
+    }
+
+    mod size_factor {
+        use super::*;
+
+        #[test]
+        fn test_caps_at_one() {
+            assert_eq!(size_factor(10_000, 100, &Kind::HtmlNative), 1.0);
+        }
+
+        #[test]
+        fn test_ignores_pass_through() {
+            assert_eq!(size_factor(1, 1_000_000, &Kind::PlainText), 1.0);
+            assert_eq!(size_factor(1, 1_000_000, &Kind::Whisper), 1.0);
+        }
+    }
+
+    mod word_ratio {
+        use super::*;
+
+        #[test]
+        fn test_ignores_numbers_and_punctuation() {
+            assert_eq!(word_ratio("In 2024, prices rose 3.5% (again)."), 1.0);
+        }
+
+        #[test]
+        fn test_counts_garbage_tokens() {
+            assert_eq!(word_ratio("hello (cid:12)(cid:34)"), 0.5);
+        }
+
+        #[test]
+        fn test_splits_unspaced_scripts() {
+            assert_eq!(word_ratio("「ネットワーク」の仕組み、です。"), 1.0);
+        }
+    }
+
+    mod boilerplate_ratio {
+        use super::*;
+
+        #[test]
+        fn test_weights_by_chars() {
+            let text = "Accept all. This sentence is regular article content.";
+            let ratio = boilerplate_ratio(text);
+            assert!(ratio > 0.0 && ratio < 0.5, "{ratio}");
+        }
+
+        #[test]
+        fn test_matches_whole_words() {
+            let text = "Open the catalog in the app. The design in this release is new.\n\
+                        Bake the cookie dough for ten minutes.";
+            assert_eq!(boilerplate_ratio(text), 0.0);
+        }
+
+        #[test]
+        fn test_ignores_punctuation_around_phrases() {
+            let text = "By clicking \"Accept All\", you consent to our use of cookies.";
+            assert_eq!(boilerplate_ratio(text), 1.0);
+        }
+    }
+}
@@ -6,7 +6,7 @@ use rodio::{Decoder, source::UniformSourceIterator};
 use whisper_rs::{FullParams, SamplingStrategy, WhisperContext, WhisperContextParameters};
 
 use crate::{
-    extractor::{EXTRACTED_CONTENT_FILENAME, Extractor},
+    extractor::{EXTRACTED_CONTENT_FILENAME, ExtractionFailure, Extractor},
     file_type::{Extension, Mime},
     validate::validate_whisper_model_path_from_env,
 };
@@ -122,7 +122,7 @@ impl Extractor for WhisperExtractor {
 
         // Write extracted text content to file
         if text.is_empty() {
-            Err(anyhow::anyhow!("whisper produced empty output"))
+            Err(anyhow::anyhow!("whisper produced empty output").context(ExtractionFailure))
         } else {
             let extracted_path = workdir.join(EXTRACTED_CONTENT_FILENAME);
             tokio::fs::write(&extracted_path, text)
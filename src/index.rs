@@ -0,0 +1,571 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fmt,
+    fs::{File, OpenOptions},
+    io::{BufRead, BufReader, Write},
+    ops::Range,
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use anyhow::Context;
+use clap::Args;
+use log::warn;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::text::is_unspaced_script;
+
+/// Schema version of the on-disk index.
+/// Bump when Record or Postings change; postings written by another version are rebuilt from records,
+/// and records are migrated in `Record::parse`.
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// Environment variable to override the state directory
+pub const STATE_DIR_ENV: &str = "NOSY_STATE_DIR";
+
+const INDEX_DIRNAME: &str = "index";
+const RECORDS_FILENAME: &str = "records.jsonl";
+const POSTINGS_FILENAME: &str = "postings.json";
+
+// BM25 parameters
+const BM25_K1: f64 = 1.2;
+const BM25_B: f64 = 0.75;
+
+// Number of terms shown around the first match in snippets
+const SNIPPET_TERMS_BEFORE: usize = 8;
+const SNIPPET_TERMS_AFTER: usize = 16;
+
+/// Options to store summaries in the local search index
+#[derive(Clone, Debug, Args)]
+pub struct IndexOptions {
+    #[arg(
+        long = "index",
+        help = "Store the summary in the local search index (searchable with `nosy find`)"
+    )]
+    pub index: bool,
+}
+
+/// A summarized run stored in the index
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Record {
+    pub version: u32,
+    pub id: String,
+    /// Seconds since UNIX epoch
+    pub indexed_at: u64,
+    /// Input path or URL
+    pub source: String,
+    pub output: PathBuf,
+    pub model: String,
+    pub summary: String,
+}
+
+impl Record {
+    pub fn new(source: &str, output: &Path, model: &str, summary: &str) -> Self {
+        let indexed_at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or_default();
+        Self {
+            version: SCHEMA_VERSION,
+            id: Uuid::new_v4().to_string(),
+            indexed_at,
+            source: source.to_string(),
+            output: output.to_path_buf(),
+            model: model.to_string(),
+            summary: summary.to_string(),
+        }
+    }
+
+    /// Parse a stored record, dispatching on its schema version
+    fn parse(line: &str) -> anyhow::Result<Self> {
+        #[derive(Deserialize)]
+        struct Versioned {
+            version: u32,
+        }
+
+        let Versioned { version } =
+            serde_json::from_str(line).context("failed to parse index record")?;
+        match version {
+            // Migrations from older versions go here
+            SCHEMA_VERSION => serde_json::from_str(line).context("failed to parse index record"),
+            _ => Err(UnsupportedRecordVersion(version).into()),
+        }
+    }
+
+    fn terms(&self) -> Vec<String> {
+        let mut terms = tokenize(&self.source);
+        terms.extend(tokenize(&self.summary));
+        terms
+    }
+}
+
+/// Error for records written with a schema version this nosy cannot read
+#[derive(Debug)]
+struct UnsupportedRecordVersion(u32);
+
+impl fmt::Display for UnsupportedRecordVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "index record has unsupported version {} (supported: {SCHEMA_VERSION}); \
+             it was probably written by a newer nosy, so upgrade nosy",
+            self.0
+        )
+    }
+}
+
+impl std::error::Error for UnsupportedRecordVersion {}
+
+/// Inverted index derived from records; can always be rebuilt from them
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct Postings {
+    version: u32,
+    /// IDs of the records the postings were built from, in record order
+    ids: Vec<String>,
+    /// Number of terms per record
+    lengths: Vec<usize>,
+    /// Term to (record position, term frequency) pairs
+    terms: HashMap<String, Vec<(usize, usize)>>,
+}
+
+impl Postings {
+    fn build(records: &[Record]) -> Self {
+        let mut postings = Postings {
+            version: SCHEMA_VERSION,
+            ..Default::default()
+        };
+        for (pos, record) in records.iter().enumerate() {
+            let terms = record.terms();
+            postings.ids.push(record.id.clone());
+            postings.lengths.push(terms.len());
+            let mut freqs: HashMap<String, usize> = HashMap::new();
+            for term in terms {
+                *freqs.entry(term).or_default() += 1;
+            }
+            for (term, freq) in freqs {
+                postings.terms.entry(term).or_default().push((pos, freq));
+            }
+        }
+        postings
+    }
+
+    /// Return true if the postings were built from exactly these records with this schema version
+    fn is_fresh(&self, records: &[Record]) -> bool {
+        self.version == SCHEMA_VERSION
+            && self.ids.len() == records.len()
+            && self
+                .ids
+                .iter()
+                .zip(records)
+                .all(|(id, record)| *id == record.id)
+    }
+
+    /// Score records against query terms with BM25
+    fn score(&self, query_terms: &HashSet<String>) -> HashMap<usize, f64> {
+        let count = self.lengths.len() as f64;
+        let avg_len = self.lengths.iter().sum::<usize>() as f64 / count.max(1.0);
+        let mut scores = HashMap::new();
+        for term in query_terms {
+            let Some(entries) = self.terms.get(term) else {
+                continue;
+            };
+            let df = entries.len() as f64;
+            let idf = (1.0 + (count - df + 0.5) / (df + 0.5)).ln();
+            for &(pos, freq) in entries {
+                let freq = freq as f64;
+                let norm = 1.0 - BM25_B + BM25_B * self.lengths[pos] as f64 / avg_len.max(1.0);
+                *scores.entry(pos).or_insert(0.0) +=
+                    idf * freq * (BM25_K1 + 1.0) / (freq + BM25_K1 * norm);
+            }
+        }
+        scores
+    }
+}
+
+/// A record matching a search query
+#[derive(Clone, Debug, Serialize)]
+pub struct Hit {
+    pub id: String,
+    pub score: f64,
+    pub indexed_at: u64,
+    pub source: String,
+    pub output: PathBuf,
+    pub model: String,
+    pub snippet: String,
+}
+
+/// Local search index of summaries.
+///
+/// Records are appended to `records.jsonl` and are the source of truth.
+/// `postings.json` is derived from them and rebuilt on search when missing, stale,
+/// or written with another schema version, so adding a record stays a single append.
+pub struct Index {
+    dir: PathBuf,
+}
+
+impl Index {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    /// Open the index under the state directory
+    pub fn from_state_dir() -> anyhow::Result<Self> {
+        Ok(Self::new(state_dir()?.join(INDEX_DIRNAME)))
+    }
+
+    /// Append a record; postings are brought up to date on the next search
+    pub fn add(&self, record: &Record) -> anyhow::Result<()> {
+        std::fs::create_dir_all(&self.dir)
+            .with_context(|| format!("failed to create index directory at '{:?}'", self.dir))?;
+
+        let records_path = self.dir.join(RECORDS_FILENAME);
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&records_path)
+            .with_context(|| format!("failed to open index records at '{records_path:?}'"))?;
+        let mut line = serde_json::to_string(record).context("failed to serialize index record")?;
+        line.push('\n');
+        // Single write so concurrent runs appending to the same file do not interleave
+        file.write_all(line.as_bytes())
+            .with_context(|| format!("failed to write index record to '{records_path:?}'"))
+    }
+
+    /// Rebuild postings from records and return the number of records
+    pub fn rebuild(&self) -> anyhow::Result<usize> {
+        let records = self.load_records()?;
+        self.write_postings(&Postings::build(&records))?;
+        Ok(records.len())
+    }
+
+    /// Search records matching the query, highest score first
+    pub fn search(&self, query: &str, limit: usize) -> anyhow::Result<Vec<Hit>> {
+        let records = self.load_records()?;
+        let postings = match self.load_postings() {
+            Some(postings) if postings.is_fresh(&records) => postings,
+            _ => {
+                let postings = Postings::build(&records);
+                if let Err(err) = self.write_postings(&postings) {
+                    warn!("Failed to write rebuilt index postings: {err:#}");
+                }
+                postings
+            }
+        };
+
+        let query_terms: HashSet<String> = tokenize(query).into_iter().collect();
+        let mut scored: Vec<(usize, f64)> = postings.score(&query_terms).into_iter().collect();
+        // Newer records first on ties
+        scored.sort_by(|(a_pos, a_score), (b_pos, b_score)| {
+            b_score.total_cmp(a_score).then(b_pos.cmp(a_pos))
+        });
+
+        Ok(scored
+            .into_iter()
+            .take(limit)
+            .map(|(pos, score)| {
+                let record = &records[pos];
+                Hit {
+                    id: record.id.clone(),
+                    score,
+                    indexed_at: record.indexed_at,
+                    source: record.source.clone(),
+                    output: record.output.clone(),
+                    model: record.model.clone(),
+                    snippet: snippet(&record.summary, &query_terms),
+                }
+            })
+            .collect())
+    }
+
+    /// Load records, skipping lines that cannot be parsed.
+    /// Records with an unsupported schema version are an error rather than skipped,
+    /// so that an older nosy does not silently drop them from results.
+    fn load_records(&self) -> anyhow::Result<Vec<Record>> {
+        let records_path = self.dir.join(RECORDS_FILENAME);
+        let file = match File::open(&records_path) {
+            Ok(file) => file,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(err) => {
+                return Err(err).with_context(|| {
+                    format!("failed to open index records at '{records_path:?}'")
+                });
+            }
+        };
+
+        let mut records = Vec::new();
+        for (idx, line) in BufReader::new(file).lines().enumerate() {
+            let line = line
+                .with_context(|| format!("failed to read index records at '{records_path:?}'"))?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            match Record::parse(&line) {
+                Ok(record) => records.push(record),
+                Err(err) if err.is::<UnsupportedRecordVersion>() => {
+                    return Err(err).with_context(|| {
+                        format!("failed to load index record at line {}", idx + 1)
+                    });
+                }
+                Err(err) => warn!(
+                    "Skipping unreadable index record at line {}: {err:#}",
+                    idx + 1
+                ),
+            }
+        }
+        Ok(records)
+    }
+
+    fn load_postings(&self) -> Option<Postings> {
+        let content = std::fs::read_to_string(self.dir.join(POSTINGS_FILENAME)).ok()?;
+        serde_json::from_str(&content).ok()
+    }
+
+    /// Write postings via a uniquely named temporary file
+    /// so readers never see a partial file and concurrent writers do not collide
+    fn write_postings(&self, postings: &Postings) -> anyhow::Result<()> {
+        std::fs::create_dir_all(&self.dir)
+            .with_context(|| format!("failed to create index directory at '{:?}'", self.dir))?;
+        let path = self.dir.join(POSTINGS_FILENAME);
+        let tmp_path = self
+            .dir
+            .join(format!("{POSTINGS_FILENAME}.{}.tmp", Uuid::new_v4()));
+        let content = serde_json::to_string(postings).context("failed to serialize postings")?;
+        std::fs::write(&tmp_path, content)
+            .with_context(|| format!("failed to write index postings to '{tmp_path:?}'"))?;
+        std::fs::rename(&tmp_path, &path).with_context(|| {
+            let _ = std::fs::remove_file(&tmp_path);
+            format!("failed to replace index postings at '{path:?}'")
+        })
+    }
+}
+
+/// Resolve the state directory.
+/// NOSY_STATE_DIR takes precedence, then XDG_STATE_HOME/nosy, then ~/.local/state/nosy.
+pub fn state_dir() -> anyhow::Result<PathBuf> {
+    if let Some(dir) = std::env::var_os(STATE_DIR_ENV).filter(|dir| !dir.is_empty()) {
+        return Ok(PathBuf::from(dir));
+    }
+    if let Some(dir) = std::env::var_os("XDG_STATE_HOME").filter(|dir| !dir.is_empty()) {
+        return Ok(PathBuf::from(dir).join("nosy"));
+    }
+    std::env::home_dir()
+        .map(|home| home.join(".local").join("state").join("nosy"))
+        .with_context(|| format!("failed to resolve state directory; set {STATE_DIR_ENV}"))
+}
+
+/// Split text into lowercase terms
+fn tokenize(text: &str) -> Vec<String> {
+    term_spans(text).into_iter().map(|(_, term)| term).collect()
+}
+
+/// Split text into lowercase terms with their byte ranges.
+/// Alphanumeric runs become one term each. Chinese and Japanese have no word separators,
+/// so their runs become overlapping character bigrams (or a single character for runs of one).
+fn term_spans(text: &str) -> Vec<(Range<usize>, String)> {
+    let mut spans = Vec::new();
+    // Start of the current alphanumeric run
+    let mut word_start = None;
+    // Start of the previous character and length of the current unspaced run
+    let mut unspaced_run: Option<(usize, usize)> = None;
+
+    // Trailing space flushes the last run
+    for (idx, c) in text
+        .char_indices()
+        .chain(std::iter::once((text.len(), ' ')))
+    {
+        let unspaced = is_unspaced_script(c);
+        if !unspaced {
+            if let Some((prev, 1)) = unspaced_run {
+                spans.push(prev..idx);
+            }
+            unspaced_run = None;
+        }
+        if (unspaced || !c.is_alphanumeric())
+            && let Some(start) = word_start.take()
+        {
+            spans.push(start..idx);
+        }
+        if unspaced {
+            unspaced_run = match unspaced_run {
+                Some((prev, len)) => {
+                    spans.push(prev..idx + c.len_utf8());
+                    Some((idx, len + 1))
+                }
+                None => Some((idx, 1)),
+            };
+        } else if c.is_alphanumeric() && word_start.is_none() {
+            word_start = Some(idx);
+        }
+    }
+
+    spans
+        .into_iter()
+        .map(|range| {
+            let term = text[range.clone()].to_lowercase();
+            (range, term)
+        })
+        .collect()
+}
+
+/// Extract text around the first query term
+fn snippet(text: &str, query_terms: &HashSet<String>) -> String {
+    let spans = term_spans(text);
+    let first_match = spans
+        .iter()
+        .position(|(_, term)| query_terms.contains(term))
+        .unwrap_or(0);
+
+    let start = first_match.saturating_sub(SNIPPET_TERMS_BEFORE);
+    let end = (first_match + SNIPPET_TERMS_AFTER).min(spans.len());
+    let from = if start == 0 { 0 } else { spans[start].0.start };
+    let to = if end == spans.len() {
+        text.len()
+    } else {
+        spans[end - 1].0.end
+    };
+
+    let mut snippet = text[from..to]
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ");
+    if from > 0 {
+        snippet.insert_str(0, "... ");
+    }
+    if to < text.len() {
+        snippet.push_str(" ...");
+    }
+    snippet
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    mod tokenize {
+        use super::*;
+
+        #[test]
+        fn test_splits_on_non_alphanumeric() {
+            assert_eq!(
+                tokenize("Kubernetes-networking: CNI & kube-proxy!"),
+                vec!["kubernetes", "networking", "cni", "kube", "proxy"]
+            );
+        }
+
+        #[test]
+        fn test_splits_unspaced_scripts_into_bigrams() {
+            assert_eq!(
+                tokenize("Kubernetesのネットワーク、DNS"),
+                vec![
+                    "kubernetes",
+                    "のネ",
+                    "ネッ",
+                    "ット",
+                    "トワ",
+                    "ワー",
+                    "ーク",
+                    "dns"
+                ]
+            );
+            assert_eq!(tokenize("AとB"), vec!["a", "と", "b"]);
+        }
+    }
+
+    mod snippet {
+        use super::*;
+
+        #[test]
+        fn test_centers_on_first_match() {
+            let text = (0..40)
+                .map(|i| {
+                    if i == 20 {
+                        "needle".to_string()
+                    } else {
+                        format!("w{i}")
+                    }
+                })
+                .collect::<Vec<_>>()
+                .join(" ");
+            let query_terms = HashSet::from(["needle".to_string()]);
+            let snippet = snippet(&text, &query_terms);
+            assert!(snippet.starts_with("... w12 "), "{snippet}");
+            assert!(snippet.contains("needle"));
+            assert!(snippet.ends_with(" w35 ..."), "{snippet}");
+        }
+    }
+
+    mod index {
+        use super::*;
+
+        #[test]
+        fn test_finds_japanese_summary() {
+            let dir = tempfile::tempdir().unwrap();
+            let index = Index::new(dir.path());
+            let record = Record::new(
+                "https://example.com/k8s",
+                Path::new("/tmp/k8s.md"),
+                "m",
+                "Kubernetesのネットワークは、CNIプラグインがPodを接続する仕組みです。",
+            );
+            index.add(&record).unwrap();
+
+            for query in ["ネットワーク", "kubernetes", "プラグイン"] {
+                let hits = index.search(query, 10).unwrap();
+                assert_eq!(hits.len(), 1, "{query}");
+                assert_eq!(hits[0].id, record.id);
+            }
+            assert!(index.search("データベース", 10).unwrap().is_empty());
+        }
+    }
+
+    mod record {
+        use super::*;
+
+        #[test]
+        fn test_parses_current_version() {
+            let record = Record::new("a", Path::new("a.md"), "m", "summary");
+            let line = serde_json::to_string(&record).unwrap();
+            assert_eq!(Record::parse(&line).unwrap(), record);
+        }
+
+        #[test]
+        fn test_rejects_unsupported_version() {
+            let mut record = Record::new("a", Path::new("a.md"), "m", "summary");
+            record.version = SCHEMA_VERSION + 1;
+            let line = serde_json::to_string(&record).unwrap();
+            let err = Record::parse(&line).unwrap_err();
+            assert!(err.is::<UnsupportedRecordVersion>(), "{err:#}");
+        }
+
+        #[test]
+        fn test_search_fails_on_unsupported_version() {
+            let dir = tempfile::tempdir().unwrap();
+            let index = Index::new(dir.path());
+            index
+                .add(&Record::new("a", Path::new("a.md"), "m", "pasta"))
+                .unwrap();
+            let mut newer = Record::new("b", Path::new("b.md"), "m", "pasta");
+            newer.version = SCHEMA_VERSION + 1;
+            index.add(&newer).unwrap();
+
+            let err = index.search("pasta", 10).unwrap_err();
+            assert!(format!("{err:#}").contains("line 2"), "{err:#}");
+        }
+    }
+
+    mod postings {
+        use super::*;
+
+        #[test]
+        fn test_ranks_more_relevant_record_first() {
+            let records = [
+                Record::new("a", Path::new("a.md"), "m", "cooking pasta at home"),
+                Record::new("b", Path::new("b.md"), "m", "pasta pasta pasta recipes"),
+            ];
+            let postings = Postings::build(&records);
+            let scores = postings.score(&HashSet::from(["pasta".to_string()]));
+            assert!(scores[&1] > scores[&0]);
+        }
+    }
+}
@@ -1,117 +1,229 @@
+use std::sync::LazyLock;
+
 use anyhow::Context;
-use clap::{Args, ValueEnum};
+use clap::{Args, ValueEnum, builder::PossibleValue};
 use genai::{
+    ModelIden, ServiceTarget,
     adapter::AdapterKind,
     chat::{ChatMessage, ChatRequest},
-    resolver::Endpoint,
+    resolver::{AuthData, Endpoint},
 };
 
-/// LLM provider type; Copy from AdapterKind in genai crate
-#[derive(Clone, Debug, ValueEnum)]
-pub enum Provider {
-    #[value(name = "github-copilot")]
-    GitHubCopilot,
+/// Environment variable for GitHub Copilot, which has no dedicated adapter in genai
+const GITHUB_COPILOT_API_KEY_ENV: &str = "GITHUB_COPILOT_API_KEY";
+
+macro_rules! define_providers {
+    (
+        $(
+            $(#[doc = $doc:literal])*
+            $variant:ident => {
+                name: $name:literal,
+                $( aliases: [$( $alias:literal ),* $(,)?], )?
+                adapter: $adapter:expr $(,)?
+            }
+        ),* $(,)?
+    ) => {
+        /// LLM provider type; Mirrors AdapterKind in genai crate
+        #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+        pub enum Provider {
+            $(
+                $(#[doc = $doc])*
+                $variant,
+            )*
+        }
+
+        impl Provider {
+            /// Name used for the CLI value and logs
+            pub fn as_str(&self) -> &'static str {
+                match self {
+                    $( Provider::$variant => $name, )*
+                }
+            }
+
+            /// genai adapter backing this provider, if any
+            fn adapter_kind(&self) -> Option<AdapterKind> {
+                match self {
+                    $( Provider::$variant => $adapter, )*
+                }
+            }
+
+            /// Former CLI names still accepted for compatibility
+            fn aliases(&self) -> &'static [&'static str] {
+                match self {
+                    $( Provider::$variant => &[$( $( $alias ),* )?], )*
+                }
+            }
+
+            fn docs(&self) -> &'static [&'static str] {
+                match self {
+                    $( Provider::$variant => &[$( $doc ),*], )*
+                }
+            }
+        }
+
+        impl ValueEnum for Provider {
+            fn value_variants<'a>() -> &'a [Self] {
+                &[$( Provider::$variant ),*]
+            }
+
+            fn to_possible_value(&self) -> Option<PossibleValue> {
+                let help = self
+                    .docs()
+                    .iter()
+                    .map(|line| line.trim())
+                    .collect::<Vec<_>>()
+                    .join(" ");
+                Some(
+                    PossibleValue::new(self.as_str())
+                        .aliases(self.aliases())
+                        .help(help),
+                )
+            }
+        }
+    };
+}
 
+// Mapping from providers to CLI names and genai adapters.
+// Keep in sync with AdapterKind in genai crate;
+// `Provider::from_adapter_kind` fails to compile when it drifts.
+define_providers! {
+    /// GitHub Models (OpenAI-compatible endpoint used by GitHub Copilot)
+    GitHubCopilot => { name: "github-copilot", adapter: None },
     /// For OpenAI Chat Completions and also can be used for OpenAI compatible APIs
     /// NOTE: This adapter share some behavior that other adapters can use while still providing some variant
-    OpenAI,
+    OpenAI => { name: "openai", aliases: ["open-ai"], adapter: Some(AdapterKind::OpenAI) },
     /// For OpenAI Responses API
-    OpenAIResp,
+    OpenAIResp => {
+        name: "openai-resp",
+        aliases: ["open-ai-resp"],
+        adapter: Some(AdapterKind::OpenAIResp),
+    },
     /// Gemini adapter supports gemini native protocol. e.g., support thinking budget.
-    Gemini,
+    Gemini => { name: "gemini", adapter: Some(AdapterKind::Gemini) },
     /// Anthopric native protocol as well
-    Anthropic,
+    Anthropic => { name: "anthropic", adapter: Some(AdapterKind::Anthropic) },
     /// For fireworks.ai, mostly OpenAI.
-    Fireworks,
+    Fireworks => { name: "fireworks", adapter: Some(AdapterKind::Fireworks) },
     /// Together AI (Mostly uses OpenAI-compatible protocol)
-    Together,
+    Together => { name: "together", adapter: Some(AdapterKind::Together) },
     /// Reuse some of the OpenAI adapter behavior, customize some (e.g., normalize thinking budget)
-    Groq,
+    Groq => { name: "groq", adapter: Some(AdapterKind::Groq) },
     /// For Mimo (Mostly use OpenAI)
-    Mimo,
+    Mimo => { name: "mimo", adapter: Some(AdapterKind::Mimo) },
     /// For Nebius (Mostly use OpenAI)
-    Nebius,
+    Nebius => { name: "nebius", adapter: Some(AdapterKind::Nebius) },
     /// For xAI (Mostly use OpenAI)
-    Xai,
+    Xai => { name: "xai", adapter: Some(AdapterKind::Xai) },
     /// For DeepSeek (Mostly use OpenAI)
-    DeepSeek,
+    DeepSeek => { name: "deepseek", aliases: ["deep-seek"], adapter: Some(AdapterKind::DeepSeek) },
     /// For ZAI (Mostly use OpenAI)
-    Zai,
+    Zai => { name: "zai", adapter: Some(AdapterKind::Zai) },
     /// For big model (only accessible via namespace bigmodel::)
-    BigModel,
+    BigModel => { name: "bigmodel", aliases: ["big-model"], adapter: Some(AdapterKind::BigModel) },
     /// Cohere today use it's own native protocol but might move to OpenAI Adapter
-    Cohere,
+    Cohere => { name: "cohere", adapter: Some(AdapterKind::Cohere) },
     /// OpenAI shared behavior + some custom. (currently, localhost only, can be customize with ServerTargetResolver).
-    Ollama,
+    Ollama => { name: "ollama", adapter: Some(AdapterKind::Ollama) },
 }
 
 impl Provider {
-    pub fn as_str(&self) -> &'static str {
+    /// Find the provider backed by the given genai adapter
+    pub fn from_adapter_kind(kind: AdapterKind) -> Self {
+        match kind {
+            AdapterKind::OpenAI => Provider::OpenAI,
+            AdapterKind::OpenAIResp => Provider::OpenAIResp,
+            AdapterKind::Gemini => Provider::Gemini,
+            AdapterKind::Anthropic => Provider::Anthropic,
+            AdapterKind::Fireworks => Provider::Fireworks,
+            AdapterKind::Together => Provider::Together,
+            AdapterKind::Groq => Provider::Groq,
+            AdapterKind::Mimo => Provider::Mimo,
+            AdapterKind::Nebius => Provider::Nebius,
+            AdapterKind::Xai => Provider::Xai,
+            AdapterKind::DeepSeek => Provider::DeepSeek,
+            AdapterKind::Zai => Provider::Zai,
+            AdapterKind::BigModel => Provider::BigModel,
+            AdapterKind::Cohere => Provider::Cohere,
+            AdapterKind::Ollama => Provider::Ollama,
+        }
+    }
+
+    /// Environment variable the API key is read from; None if no key is required
+    pub fn api_key_env_name(&self) -> Option<&'static str> {
         match self {
-            Provider::GitHubCopilot => "github-copilot",
-            Provider::OpenAI => "openai",
-            Provider::OpenAIResp => "openai-resp",
-            Provider::Gemini => "gemini",
-            Provider::Anthropic => "anthropic",
-            Provider::Fireworks => "fireworks",
-            Provider::Together => "together",
-            Provider::Groq => "groq",
-            Provider::Mimo => "mimo",
-            Provider::Nebius => "nebius",
-            Provider::Xai => "xai",
-            Provider::DeepSeek => "deepseek",
-            Provider::Zai => "zai",
-            Provider::BigModel => "bigmodel",
-            Provider::Cohere => "cohere",
-            Provider::Ollama => "ollama",
+            Provider::GitHubCopilot => Some(GITHUB_COPILOT_API_KEY_ENV),
+            _ => self.adapter_kind()?.default_key_env_name(),
         }
     }
 }
 
+impl TryFrom<Provider> for AdapterKind {
+    type Error = anyhow::Error;
+
+    fn try_from(provider: Provider) -> anyhow::Result<Self> {
+        provider.adapter_kind().with_context(|| {
+            format!(
+                "provider '{}' has no dedicated genai adapter",
+                provider.as_str()
+            )
+        })
+    }
+}
+
+/// Long help for --provider, generated from the provider mapping
+/// so that the listed names and environment variables cannot drift.
+static PROVIDER_LONG_HELP: LazyLock<String> = LazyLock::new(|| {
+    let mut help = String::from(
+        r#"LLM service provider.
+
+API keys are resolved from environment variables according to genai conventions.
+- If --provider is specified: use that provider's adapter and its environment variable
+- If omitted: infer the adapter from the model name and use its default environment variable
+  - If this fails, try specifying --provider explicitly.
+
+Environment variables per provider:"#,
+    );
+    for provider in Provider::value_variants() {
+        let env = provider
+            .api_key_env_name()
+            .unwrap_or("(no API key required)");
+        help.push_str(&format!("\n  - {}: {env}", provider.as_str()));
+    }
+    help
+});
+
 /// Options to construct LLM client
 #[derive(Clone, Debug, Args)]
 pub struct LLMConstructionOptions {
     #[arg(
         long = "provider",
         help = "LLM service provider",
-        long_help = r#"LLM service provider.
-
-API keys are resolved from environment variables according to genai conventions.
-- If --provider is specified: use the default environment variable for that adapter
-- If omitted: infer the adapter from the model name and use its default environment variable
-  - If this fails, try specifying --provider explicitly.
-
-Default environment variables (genai crate):
-  - OpenAI / OpenAIResp: OPENAI_API_KEY
-  - Anthropic: ANTHROPIC_API_KEY
-  - Gemini: GEMINI_API_KEY
-  - Fireworks: FIREWORKS_API_KEY
-  - Together: TOGETHER_API_KEY
-  - Groq: GROQ_API_KEY
-  - Mimo: MIMO_API_KEY
-  - Nebius: NEBIUS_API_KEY
-  - xAI: XAI_API_KEY
-  - DeepSeek: DEEPSEEK_API_KEY
-  - ZAI: ZAI_API_KEY
-  - BigModel: BIGMODEL_API_KEY
-  - Cohere: COHERE_API_KEY
-  - Ollama: (no API key required)
-
-For the following AI providers, nosy uses a dedicated endpoint and reads a dedicated environment variable:
-  - GitHub Copilot: GITHUB_COPILOT_API_KEY"#
+        long_help = PROVIDER_LONG_HELP.as_str()
     )]
     pub provider: Option<Provider>,
 }
 
 /// Create LLM client from LLMOptions
 pub fn create_llm_client(opts: &LLMConstructionOptions) -> anyhow::Result<genai::Client> {
-    let opts = opts.clone();
+    let provider = opts.provider;
     Ok(genai::Client::builder()
-        .with_service_target_resolver_fn(move |mut target: genai::ServiceTarget| {
-            if let Some(Provider::GitHubCopilot) = &opts.provider {
+        // Switch the adapter before genai resolves its default endpoint,
+        // so that the endpoint matches the given provider rather than the model name
+        .with_model_mapper_fn(
+            move |model: ModelIden| match provider.map(AdapterKind::try_from) {
+                Some(Ok(kind)) => Ok(ModelIden::new(kind, model.model_name)),
+                _ => Ok(model),
+            },
+        )
+        .with_service_target_resolver_fn(move |mut target: ServiceTarget| {
+            let Some(provider) = provider else {
+                return Ok(target);
+            };
+            if provider == Provider::GitHubCopilot {
                 target.endpoint = Endpoint::from_static("https://models.inference.ai.azure.com");
-                target.auth =
-                    genai::resolver::AuthData::FromEnv("GITHUB_COPILOT_API_KEY".to_string());
+            }
+            if let Some(env) = provider.api_key_env_name() {
+                target.auth = AuthData::FromEnv(env.to_string());
             }
             Ok(target)
         })
@@ -151,3 +263,93 @@ pub async fn chat_request(
         .map(|s| s.to_string())
         .context("LLM returned no text")
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    mod provider {
+        use super::*;
+
+        #[test]
+        fn test_round_trips_adapter_kind() {
+            for provider in Provider::value_variants() {
+                if let Ok(kind) = AdapterKind::try_from(*provider) {
+                    assert_eq!(Provider::from_adapter_kind(kind), *provider);
+                }
+            }
+        }
+
+        #[test]
+        fn test_try_from_without_adapter() {
+            assert!(AdapterKind::try_from(Provider::GitHubCopilot).is_err());
+        }
+
+        #[test]
+        fn test_cli_name_matches_as_str() {
+            for provider in Provider::value_variants() {
+                assert_eq!(
+                    Provider::from_str(provider.as_str(), false).ok(),
+                    Some(*provider)
+                );
+            }
+        }
+
+        #[test]
+        fn test_accepts_former_cli_names() {
+            for (alias, provider) in [
+                ("open-ai", Provider::OpenAI),
+                ("open-ai-resp", Provider::OpenAIResp),
+                ("deep-seek", Provider::DeepSeek),
+                ("big-model", Provider::BigModel),
+            ] {
+                assert_eq!(Provider::from_str(alias, false).ok(), Some(provider));
+            }
+        }
+
+        #[test]
+        fn test_long_help_lists_every_provider() {
+            for provider in Provider::value_variants() {
+                assert!(PROVIDER_LONG_HELP.contains(&format!("  - {}: ", provider.as_str())));
+            }
+        }
+    }
+
+    mod create_llm_client {
+        use super::*;
+
+        async fn resolve(provider: Option<Provider>, model: &str) -> ServiceTarget {
+            create_llm_client(&LLMConstructionOptions { provider })
+                .unwrap()
+                .resolve_service_target(model)
+                .await
+                .unwrap()
+        }
+
+        #[tokio::test]
+        async fn test_infers_adapter_without_provider() {
+            let target = resolve(None, "gpt-4o").await;
+            assert_eq!(target.model.adapter_kind, AdapterKind::OpenAI);
+        }
+
+        #[tokio::test]
+        async fn test_selects_provider_adapter_and_key() {
+            let target = resolve(Some(Provider::Groq), "gpt-4o").await;
+            assert_eq!(target.model.adapter_kind, AdapterKind::Groq);
+            assert!(matches!(target.auth, AuthData::FromEnv(env) if env == "GROQ_API_KEY"));
+        }
+
+        #[tokio::test]
+        async fn test_github_copilot_endpoint_and_key() {
+            let target = resolve(Some(Provider::GitHubCopilot), "gpt-4o").await;
+            assert_eq!(target.model.adapter_kind, AdapterKind::OpenAI);
+            assert_eq!(
+                target.endpoint.base_url(),
+                "https://models.inference.ai.azure.com"
+            );
+            assert!(
+                matches!(target.auth, AuthData::FromEnv(env) if env == GITHUB_COPILOT_API_KEY_ENV)
+            );
+        }
+    }
+}
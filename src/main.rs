@@ -2,6 +2,7 @@ use std::{
     collections::HashMap,
     fmt,
     path::{Path, PathBuf},
+    process::ExitCode,
     sync::LazyLock,
     time::Duration,
 };
@@ -9,7 +10,7 @@ use std::{
 use anyhow::Context;
 use clap::{Args, CommandFactory, Parser, Subcommand, ValueEnum, error::ErrorKind};
 use indicatif::{ProgressBar, ProgressStyle};
-use log::{debug, info};
+use log::{debug, info, warn};
 use uuid::Uuid;
 use validator::Validate;
 
@@ -18,21 +19,28 @@ mod cli_command;
 mod extractor;
 mod fetcher;
 mod file_type;
+mod index;
 mod llm;
 mod message;
 mod scheme;
+mod text;
 mod validate;
 
 use crate::{
-    auxiliary::{CompletionArgs, DownloadWhisperArgs},
+    auxiliary::{CompletionArgs, DownloadWhisperArgs, FindArgs},
     extractor::{
-        Extractor, html::HtmlExtractor, pandoc::PandocExtractor, pdf::PdfExtractor,
+        EXTRACTION_FAILURE_EXIT_CODE, ExtractionFailure, Extractor,
+        html::HtmlExtractor,
+        pandoc::PandocExtractor,
+        pdf::PdfExtractor,
+        quality::{ContentGuardOptions, MIN_QUALITY_SCORE, QualityReport, ThinReason},
         whisper::WhisperExtractor,
     },
     fetcher::{
         Fetcher,
         http::{HttpFetcher, HttpFetcherOptions},
     },
+    index::{Index, IndexOptions, Record},
     llm::{LLMConstructionOptions, LLMRequestOptions},
     message::{ChatMessageOptions, SystemChatMessageVariables},
     scheme::InputScheme,
@@ -122,6 +130,9 @@ enum Command {
     /// Download Whisper model to a specified path
     #[command(name = "download-whisper")]
     DownloadWhisper(DownloadWhisperArgs),
+    /// Search summaries stored in the local index (see --index)
+    #[command(name = "find")]
+    Find(FindArgs),
 }
 
 #[derive(Clone, Debug, Args, Validate)]
@@ -148,6 +159,9 @@ struct SummarizeArgs {
     #[command(flatten)]
     extract_args: ExtractArgs,
 
+    #[command(flatten)]
+    content_guard_opts: ContentGuardOptions,
+
     #[command(flatten)]
     llm_construction_opts: LLMConstructionOptions,
 
@@ -159,6 +173,9 @@ struct SummarizeArgs {
 
     #[command(flatten)]
     system_chat_message_vars: SystemChatMessageVariables,
+
+    #[command(flatten)]
+    index_opts: IndexOptions,
 }
 
 const FETCH_COLOR_HEX: &str = "#FFEADB";
@@ -173,35 +190,54 @@ static SUMMARIZE_SPINNER_TEMPLATE: LazyLock<String> =
     LazyLock::new(|| format!("{{spinner:.{SUMMARIZE_COLOR_HEX}}} {{msg}} [{{elapsed}}]"));
 
 #[tokio::main]
-async fn main() -> anyhow::Result<()> {
+async fn main() -> ExitCode {
+    match run().await {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            // Same format as returning the error from main
+            eprintln!("Error: {err:?}");
+            if err.downcast_ref::<ExtractionFailure>().is_some() {
+                ExitCode::from(EXTRACTION_FAILURE_EXIT_CODE)
+            } else {
+                ExitCode::FAILURE
+            }
+        }
+    }
+}
+
+async fn run() -> anyhow::Result<()> {
     let Cli {
         command,
         summarize_args,
     } = Cli::parse();
 
-    // Handle auxiliary commands early (e.g., completion)
-    // so main stays focused on extract/summarize.
-    if auxiliary::handle_auxiliary_command(command.as_ref()).await? {
-        return Ok(());
-    }
-
     let extract_summarize_args = match &command {
-        Some(Command::Extract(args)) => &args.extract_summarize_args,
-        Some(Command::Summarize(args)) => &args.extract_args.extract_summarize_args,
-        None => &summarize_args.extract_args.extract_summarize_args,
-        Some(Command::Completion(_)) | Some(Command::DownloadWhisper(_)) => {
-            unreachable!("auxiliary commands handled earlier")
-        }
+        Some(Command::Extract(args)) => Some(&args.extract_summarize_args),
+        Some(Command::Summarize(args)) => Some(&args.extract_args.extract_summarize_args),
+        None => Some(&summarize_args.extract_args.extract_summarize_args),
+        Some(Command::Completion(_))
+        | Some(Command::DownloadWhisper(_))
+        | Some(Command::Find(_)) => None,
     };
 
     // Enable RUST_LOG environment variable support
     // For developpers, we can set RUST_LOG=debug to see debug logs from dependencies.
+    // Auxiliary commands have no --log-level, so only their warnings are shown.
+    let log_level = extract_summarize_args.map_or(LogLevel::Warn, |args| args.log_level);
     env_logger::Builder::from_env(
-        env_logger::Env::default()
-            .default_filter_or(format!("nosy={}", extract_summarize_args.log_level)),
+        env_logger::Env::default().default_filter_or(format!("nosy={log_level}")),
     )
     .init();
 
+    // Handle auxiliary commands early (e.g., completion)
+    // so main stays focused on extract/summarize.
+    if auxiliary::handle_auxiliary_command(command.as_ref()).await? {
+        return Ok(());
+    }
+    let Some(extract_summarize_args) = extract_summarize_args else {
+        unreachable!("auxiliary commands handled earlier")
+    };
+
     debug!("Extract/summarize arguments: {:?}", extract_summarize_args);
 
     // 0. Validate arguments
@@ -334,7 +370,16 @@ async fn main() -> anyhow::Result<()> {
         _ => unreachable!("auxiliary and extract commands handled earlier"),
     };
 
-    // 5. Summarize content
+    // 5. Check extracted content quality before spending tokens
+    check_content_quality(
+        &raw_content_path,
+        &extracted_content_path,
+        &extractor_kind,
+        &summarize_args.content_guard_opts,
+    )
+    .await?;
+
+    // 6. Summarize content
     let summary = summarize(
         &extracted_content_path,
         summarize_args,
@@ -344,13 +389,40 @@ async fn main() -> anyhow::Result<()> {
     let summary_chars = summary.chars().count();
     debug!("Received summary from LLM: chars={summary_chars}");
 
-    // 6. Write output
+    // 7. Write output
     create_parent_dirs(output).await?;
-    tokio::fs::write(output, summary)
+    tokio::fs::write(output, &summary)
         .await
         .with_context(|| format!("failed to write summary to output path '{output:?}'"))?;
     debug!("Wrote summary to output path: {output:?}");
 
+    // 8. Store summary in the local index (opt-in)
+    // Indexing is best-effort and never fails the run.
+    if summarize_args.index_opts.index {
+        // Record local paths as absolute so they stay meaningful from any directory.
+        // file:// inputs are kept as-is since they are already absolute URLs.
+        let source = match scheme {
+            InputScheme::File if !input.contains("://") => std::path::absolute(input)
+                .map(|path| path.to_string_lossy().into_owned())
+                .unwrap_or_else(|_| input.to_string()),
+            _ => input.to_string(),
+        };
+        let record = Record::new(
+            &source,
+            &std::path::absolute(output).unwrap_or_else(|_| output.to_path_buf()),
+            &summarize_args.llm_request_opts.model,
+            &summary,
+        );
+        let id = record.id.clone();
+        let stored =
+            tokio::task::spawn_blocking(move || Index::from_state_dir()?.add(&record)).await;
+        match stored {
+            Ok(Ok(())) => debug!("Stored summary in local index: id={id}"),
+            Ok(Err(err)) => warn!("Failed to store summary in local index: {err:#}"),
+            Err(err) => warn!("Failed to store summary in local index: {err}"),
+        }
+    }
+
     Ok(())
 }
 
@@ -475,6 +547,59 @@ async fn extract(
     }
 }
 
+/// Return error if extracted content looks too thin or garbled to be worth summarizing
+async fn check_content_quality(
+    raw_content_path: &Path,
+    extracted_content_path: &Path,
+    extractor_kind: &extractor::Kind,
+    opts: &ContentGuardOptions,
+) -> anyhow::Result<()> {
+    let input_bytes = tokio::fs::metadata(raw_content_path)
+        .await
+        .with_context(|| format!("failed to read metadata of '{raw_content_path:?}'"))?
+        .len();
+    let content = tokio::fs::read_to_string(extracted_content_path)
+        .await
+        .with_context(|| {
+            format!("failed to read extracted content from '{extracted_content_path:?}'")
+        })?;
+
+    let report = QualityReport::evaluate(&content, input_bytes, extractor_kind);
+    debug!("Extracted content quality: {report:?}");
+    let Some(reason) = report.thin_reason(opts.min_content_chars) else {
+        return Ok(());
+    };
+    let (problem, override_hint) = match reason {
+        ThinReason::TooShort => (
+            format!(
+                "extracted content is too short to summarize ({} chars, minimum {})",
+                report.chars, opts.min_content_chars
+            ),
+            "lower --min-content-chars",
+        ),
+        ThinReason::LowScore => (
+            format!(
+                "extracted content looks garbled or mostly boilerplate (quality score {:.2}, minimum {MIN_QUALITY_SCORE})",
+                report.score
+            ),
+            "pass --allow-thin-content",
+        ),
+    };
+    if opts.allow_thin_content {
+        warn!("{problem}; summarizing anyway");
+        return Ok(());
+    }
+    Err(anyhow::anyhow!(
+        concat!(
+            "{}. Try another extractor via --ext-kind or headless fetching via --http-fetch-mode headless, ",
+            "or {} to summarize anyway."
+        ),
+        problem,
+        override_hint,
+    )
+    .context(ExtractionFailure))
+}
+
 /// Summarize extracted content using LLM
 async fn summarize(
     content_path: &Path,
@@ -494,9 +619,8 @@ async fn summarize(
     let provider_label = summarize_args
         .llm_construction_opts
         .provider
-        .as_ref()
-        .map(llm::Provider::as_str)
-        .unwrap_or_else(|| adapter_kind.as_lower_str());
+        .unwrap_or_else(|| llm::Provider::from_adapter_kind(adapter_kind))
+        .as_str();
     info!(
         "LLM request: model='{model}', provider='{}'",
         provider_label
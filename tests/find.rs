@@ -0,0 +1,172 @@
+use std::{
+    path::{Path, PathBuf},
+    process::{Command, Output},
+};
+
+use tempfile::TempDir;
+
+const FIXTURE_RECORDS: &str = concat!(
+    env!("CARGO_MANIFEST_DIR"),
+    "/tests/fixtures/index/records.jsonl"
+);
+
+/// Create a state directory whose index only holds replayed run records,
+/// so postings are built by nosy itself on first use.
+fn replay_state_dir() -> TempDir {
+    let state_dir = tempfile::tempdir().expect("failed to create temp dir");
+    let index_dir = state_dir.path().join("index");
+    std::fs::create_dir_all(&index_dir).expect("failed to create index dir");
+    std::fs::copy(FIXTURE_RECORDS, index_dir.join("records.jsonl"))
+        .expect("failed to copy fixture records");
+    state_dir
+}
+
+fn postings_path(state_dir: &Path) -> PathBuf {
+    state_dir.join("index").join("postings.json")
+}
+
+fn nosy_find(state_dir: &Path, args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_nosy"))
+        .arg("find")
+        .args(args)
+        .env("NOSY_STATE_DIR", state_dir)
+        .output()
+        .expect("failed to run nosy")
+}
+
+fn find_json(state_dir: &Path, args: &[&str]) -> Vec<serde_json::Value> {
+    let mut args = args.to_vec();
+    args.push("--json");
+    let output = nosy_find(state_dir, &args);
+    assert!(output.status.success(), "{output:?}");
+    serde_json::from_slice(&output.stdout).expect("failed to parse JSON output")
+}
+
+mod find {
+    use super::*;
+
+    #[test]
+    fn test_ranks_matching_runs() {
+        let state_dir = replay_state_dir();
+        let hits = find_json(state_dir.path(), &["kubernetes networking"]);
+
+        assert_eq!(hits.len(), 2);
+        assert_eq!(
+            hits[0]["source"],
+            "https://example.com/blog/kubernetes-networking-explained"
+        );
+        assert_eq!(hits[0]["output"], "/home/user/summaries/k8s-networking.md");
+        assert!(hits[0]["score"].as_f64().unwrap() > hits[1]["score"].as_f64().unwrap());
+        assert!(
+            hits[0]["snippet"]
+                .as_str()
+                .unwrap()
+                .starts_with("Kubernetes gives every pod")
+        );
+        assert_eq!(hits[1]["source"], "/home/user/papers/service-mesh.pdf");
+    }
+
+    #[test]
+    fn test_limit() {
+        let state_dir = replay_state_dir();
+        let hits = find_json(state_dir.path(), &["kubernetes", "--limit", "1"]);
+        assert_eq!(hits.len(), 1);
+    }
+
+    #[test]
+    fn test_no_match() {
+        let state_dir = replay_state_dir();
+        let hits = find_json(state_dir.path(), &["quantum chromodynamics"]);
+        assert!(hits.is_empty());
+    }
+
+    #[test]
+    fn test_empty_state_dir() {
+        let state_dir = tempfile::tempdir().expect("failed to create temp dir");
+        let hits = find_json(state_dir.path(), &["kubernetes"]);
+        assert!(hits.is_empty());
+    }
+
+    #[test]
+    fn test_text_output() {
+        let state_dir = replay_state_dir();
+        let output = nosy_find(state_dir.path(), &["sourdough"]);
+        assert!(output.status.success(), "{output:?}");
+
+        let stdout = String::from_utf8(output.stdout).unwrap();
+        assert!(stdout.contains("https://example.com/recipes/sourdough"));
+        assert!(stdout.contains("output: /home/user/summaries/sourdough.md"));
+    }
+
+    #[test]
+    fn test_rebuilds_outdated_postings() {
+        let state_dir = replay_state_dir();
+        let postings = postings_path(state_dir.path());
+        // Postings built from the same records by an older schema version
+        let outdated = serde_json::json!({
+            "version": 0,
+            "ids": [
+                "5b0f3c1e-6a0e-4d0c-9a51-0c1f6f3d2a01",
+                "5b0f3c1e-6a0e-4d0c-9a51-0c1f6f3d2a02",
+                "5b0f3c1e-6a0e-4d0c-9a51-0c1f6f3d2a03",
+                "5b0f3c1e-6a0e-4d0c-9a51-0c1f6f3d2a04",
+            ],
+            "lengths": [1, 1, 1, 1],
+            "terms": {},
+        });
+        std::fs::write(&postings, outdated.to_string()).unwrap();
+
+        let hits = find_json(state_dir.path(), &["tokio"]);
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0]["source"], "https://example.com/talks/rust-async");
+
+        let rebuilt: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(&postings).unwrap()).unwrap();
+        assert_eq!(rebuilt["version"], 1);
+        assert_ne!(rebuilt["terms"], outdated["terms"]);
+    }
+
+    #[test]
+    fn test_warns_on_unreadable_record() {
+        let state_dir = replay_state_dir();
+        let records = state_dir.path().join("index").join("records.jsonl");
+        let mut content = std::fs::read_to_string(&records).unwrap();
+        content.push_str("{\"version\": 1, \"id\": \"trunc\n");
+        std::fs::write(&records, content).unwrap();
+
+        let output = nosy_find(state_dir.path(), &["sourdough"]);
+        assert!(output.status.success(), "{output:?}");
+        assert!(
+            String::from_utf8_lossy(&output.stderr).contains("Skipping unreadable index record"),
+            "{output:?}"
+        );
+    }
+
+    #[test]
+    fn test_unsupported_record_version() {
+        let state_dir = replay_state_dir();
+        let records = state_dir.path().join("index").join("records.jsonl");
+        let content = std::fs::read_to_string(&records).unwrap().replacen(
+            r#""version": 1"#,
+            r#""version": 99"#,
+            1,
+        );
+        std::fs::write(&records, content).unwrap();
+
+        let output = nosy_find(state_dir.path(), &["sourdough"]);
+        assert!(!output.status.success(), "{output:?}");
+        assert!(
+            String::from_utf8_lossy(&output.stderr).contains("unsupported version 99"),
+            "{output:?}"
+        );
+    }
+
+    #[test]
+    fn test_rebuild_without_query() {
+        let state_dir = replay_state_dir();
+        let output = nosy_find(state_dir.path(), &["--rebuild"]);
+        assert!(output.status.success(), "{output:?}");
+        assert!(String::from_utf8_lossy(&output.stderr).contains("4 records"));
+        assert!(postings_path(state_dir.path()).exists());
+    }
+}